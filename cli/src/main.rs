@@ -48,10 +48,18 @@
 )]
 #![forbid(unsafe_code)]
 
-use clap::{Args, Parser, ValueHint};
-use random_gradient_generator::{NoiseOptions, PixelInit, Size};
+use clap::{Args, Parser, Subcommand, ValueEnum, ValueHint};
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use random_gradient_generator::{
+	color::ColorModel,
+	led::{PanelGeometry, PanelLayout, StreamOptions},
+	raster::ColorDepth,
+	ChannelInit, NoiseOptions, PixelInit, Size,
+};
 use std::{
 	fmt::{self, Display, Formatter},
+	net::SocketAddr,
 	path::PathBuf,
 	str::FromStr,
 };
@@ -60,18 +68,156 @@ use std::{
 #[derive(Debug, Clone, Parser)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
-	/// Path to the output image
-	#[arg(value_name = "PATH", value_hint = ValueHint::FilePath)]
-	output: PathBuf,
 	/// Size of the image in pixels (format: `WxH`, e.g. `512x256`)
 	#[arg(short, long)]
 	size: Size,
+	/// Color model the pixel color options below are interpreted in
+	#[arg(long, value_name = "MODEL", default_value_t = CliColorModel::Hsv)]
+	color_model: CliColorModel,
+	/// Master seed every random value (the per-channel noise seeds, when not set explicitly) is deterministically
+	/// derived from
+	///
+	/// The effective seed is always printed, so any randomly-seeded image can be regenerated bit-for-bit by passing
+	/// it back in here.
+	#[arg(long, value_name = "SEED")]
+	seed: Option<u64>,
 	/// Argument group related to pixel colors
 	#[command(flatten, next_help_heading = "Pixel color options")]
 	color: CliColor,
 	/// Argument group related to the noise
 	#[command(flatten, next_help_heading = "Noise options")]
 	noise: CliNoise,
+	/// How to output the generated gradient
+	#[command(subcommand)]
+	output: CliOutput,
+}
+
+/// Color model the pixel color options are interpreted in
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum CliColorModel {
+	/// Hue, saturation, brightness (a.k.a. value)
+	#[default]
+	Hsv,
+	/// Hue, saturation, lightness
+	Hsl,
+	/// OKLab `a`/`b` chroma, and lightness; yields perceptually uniform gradients
+	Oklab,
+}
+impl Display for CliColorModel {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Hsv => f.write_str("hsv"),
+			Self::Hsl => f.write_str("hsl"),
+			Self::Oklab => f.write_str("oklab"),
+		}
+	}
+}
+impl From<CliColorModel> for ColorModel {
+	#[inline]
+	fn from(model: CliColorModel) -> Self {
+		match model {
+			CliColorModel::Hsv => Self::Hsv,
+			CliColorModel::Hsl => Self::Hsl,
+			CliColorModel::Oklab => Self::Oklab,
+		}
+	}
+}
+impl CliColorModel {
+	/// Default `--saturation` (HSV/HSL) or OKLab `b` chroma (OKLab) value, used when the user doesn't pass
+	/// `--saturation` explicitly
+	///
+	/// HSV/HSL's full saturation (`1.0`) is fine for those two models, but is well outside OKLab's valid
+	/// `-0.4..=0.4` chroma range, so OKLab gets its own, in-gamut default instead.
+	const fn default_saturation(self) -> f32 {
+		match self {
+			Self::Hsv | Self::Hsl => 1.0,
+			Self::Oklab => 0.2,
+		}
+	}
+
+	/// Default `--brightness` (HSV) or lightness (HSL/OKLab) value, used when the user doesn't pass
+	/// `--brightness` explicitly
+	///
+	/// Full lightness (`1.0`) is valid in every model, but forces HSL to white regardless of hue/saturation and puts
+	/// OKLab colors with non-zero `a`/`b` chroma outside the sRGB gamut, so those two models default to mid lightness
+	/// instead.
+	const fn default_brightness(self) -> f32 {
+		match self {
+			Self::Hsv => 1.0,
+			Self::Hsl | Self::Oklab => 0.5,
+		}
+	}
+}
+
+/// Bits per color channel an output image is encoded at
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum CliColorDepth {
+	/// 8 bits per channel
+	#[default]
+	Eight,
+	/// 16 bits per channel
+	Sixteen,
+}
+impl Display for CliColorDepth {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Eight => f.write_str("8"),
+			Self::Sixteen => f.write_str("16"),
+		}
+	}
+}
+impl From<CliColorDepth> for ColorDepth {
+	#[inline]
+	fn from(depth: CliColorDepth) -> Self {
+		match depth {
+			CliColorDepth::Eight => Self::Eight,
+			CliColorDepth::Sixteen => Self::Sixteen,
+		}
+	}
+}
+
+/// How to output the generated gradient
+#[derive(Debug, Clone, Subcommand)]
+enum CliOutput {
+	/// Write a single image to a file
+	File {
+		/// Path to the output image
+		///
+		/// The output format is picked from the extension: `.png` is written as PNG, anything else as BMP.
+		#[arg(value_name = "PATH", value_hint = ValueHint::FilePath)]
+		path: PathBuf,
+		/// Bits per color channel to encode the image at
+		///
+		/// Only honored by output formats that support it (currently only PNG); other formats always
+		/// write 8 bits per channel regardless.
+		#[arg(long, value_name = "DEPTH", default_value_t = CliColorDepth::Eight)]
+		depth: CliColorDepth,
+	},
+	/// Stream animated frames as raw RGB packets over UDP, for addressable LED matrices
+	Stream(CliStream),
+}
+
+/// Argument group related to UDP frame streaming
+#[derive(Debug, Clone, Args)]
+struct CliStream {
+	/// Address of the remote receiver
+	#[arg(long, value_name = "IP:PORT")]
+	remote: SocketAddr,
+	/// Number of frames sent per second
+	#[arg(long, value_name = "FLOAT", default_value_t = 30.0)]
+	frame_rate: f32,
+	/// Number of frames to generate
+	#[arg(long, value_name = "INT", default_value_t = 600)]
+	frames: u32,
+	/// How much the noise's time axis advances between two successive frames
+	#[arg(long, value_name = "FLOAT", default_value_t = 0.05)]
+	time_step: f32,
+	/// Wire alternate rows in the opposite direction (serpentine/zig-zag), instead of every row left-to-right
+	#[arg(long)]
+	serpentine: bool,
+	/// How long to wait (in seconds) for a per-frame completion response before giving up
+	#[arg(long, value_name = "SECONDS")]
+	response_timeout: Option<f32>,
 }
 
 /// Possible states for arguments of [`CliColor`]
@@ -115,76 +261,97 @@ impl Display for ColorParameter {
 }
 
 /// Argument group related to pixel colors
+///
+/// Each of [`hue`](Self::hue), [`saturation`](Self::saturation) and [`brightness`](Self::brightness) may
+/// independently be set to [`ColorParameter::Random`], in which case its matching `*_seed` argument (randomly
+/// choosen if not specified) seeds that channel's own noise field. What these three options actually feed into
+/// depends on `--color-model`: for `hsv`/`hsl` they are exactly hue/saturation/brightness (or lightness); for
+/// `oklab`, `hue` and `saturation` become the `a`/`b` chroma and `brightness` becomes lightness.
+///
+/// [`saturation`](Self::saturation) and [`brightness`](Self::brightness) have no single default that is valid
+/// (let alone sensible) across all three models, so they're left unset here when the user doesn't pass them and
+/// resolved against [`CliColorModel::default_saturation`]/[`CliColorModel::default_brightness`] in [`main`] instead.
 #[derive(Debug, Clone, Copy, Args)]
 struct CliColor {
-	/// Hue component of the colors (range: 0 < hue ≤ 360)
+	/// Hue (hsv/hsl) or OKLab `a` chroma (oklab) component of the colors (range: 0 ≤ hue < 360, or -0.4 ≤ a ≤ 0.4)
 	#[arg(long, value_name = "FLOAT", default_value_t)]
 	hue: ColorParameter,
-	/// Saturation component of the colors (range: 0 ≤ saturation ≤ 1)
-	#[arg(
-		long,
-		value_name = "FLOAT",
-		default_value_t = ColorParameter::Set(1.0)
-	)]
+	/// Value that changes the output of the hue channel's noise, when randomized; drawn from `--seed` if unset
+	#[arg(long, value_name = "INT")]
+	hue_seed: Option<i32>,
+	/// Saturation (hsv/hsl) or OKLab `b` chroma (oklab) component of the colors (range: 0 ≤ saturation ≤ 1, or -0.4 ≤ b ≤ 0.4)
+	///
+	/// Defaults to a value appropriate for `--color-model` when not set (see `CliColorModel::default_saturation`).
+	#[arg(long, value_name = "FLOAT")]
+	saturation: Option<ColorParameter>,
+	/// Value that changes the output of the saturation channel's noise, when randomized; drawn from `--seed` if unset
+	#[arg(long, value_name = "INT")]
+	saturation_seed: Option<i32>,
+	/// Brightness (hsv) or lightness (hsl/oklab) component of the colors (range: 0 ≤ brightness ≤ 1)
+	///
+	/// Defaults to a value appropriate for `--color-model` when not set (see `CliColorModel::default_brightness`).
+	#[arg(long, value_name = "FLOAT")]
+	brightness: Option<ColorParameter>,
+	/// Value that changes the output of the brightness channel's noise, when randomized; drawn from `--seed` if unset
+	#[arg(long, value_name = "INT")]
+	brightness_seed: Option<i32>,
+}
+
+/// Builds a [`PixelInit`] from `cli`, drawing any unset `*_seed` from `rng` rather than from `cli`
+///
+/// `saturation`/`brightness` are taken as already-resolved parameters (see [`CliColor`]'s doc) rather than read off
+/// `cli` directly, since resolving them requires knowing `--color-model`.
+fn pixel_init_from_cli(
+	cli: CliColor,
 	saturation: ColorParameter,
-	/// Brightness component of the colors (range: 0 ≤ brightness ≤ 1)
-	#[arg(
-		long,
-		value_name = "FLOAT",
-		default_value_t = ColorParameter::Set(1.0)
-	)]
 	brightness: ColorParameter,
+	rng: &mut ChaCha8Rng,
+) -> PixelInit {
+	PixelInit {
+		hue: channel_init_from_cli(cli.hue, cli.hue_seed, rng),
+		saturation: channel_init_from_cli(saturation, cli.saturation_seed, rng),
+		brightness: channel_init_from_cli(brightness, cli.brightness_seed, rng),
+	}
 }
-impl From<CliColor> for PixelInit {
-	#[inline]
-	fn from(cli: CliColor) -> Self {
-		match cli {
-			CliColor {
-				hue: ColorParameter::Random,
-				saturation: ColorParameter::Set(saturation),
-				brightness: ColorParameter::Set(brightness),
-			} => PixelInit::Hue {
-				saturation,
-				brightness,
-			},
-			CliColor {
-				hue: ColorParameter::Set(hue),
-				saturation: ColorParameter::Random,
-				brightness: ColorParameter::Set(brightness),
-			} => PixelInit::Saturation { hue, brightness },
-			CliColor {
-				hue: ColorParameter::Set(hue),
-				saturation: ColorParameter::Set(saturation),
-				brightness: ColorParameter::Random,
-			} => PixelInit::Brightness { hue, saturation },
-			_ => {
-				panic!(
-					"{cli:?} must have exactly one component set to {:?}",
-					ColorParameter::Random
-				);
-			}
-		}
+
+/// Builds a single [`ChannelInit`] from a `CliColor` field, drawing its seed from `rng` if `seed` is unset
+fn channel_init_from_cli(parameter: ColorParameter, seed: Option<i32>, rng: &mut ChaCha8Rng) -> ChannelInit {
+	match parameter {
+		ColorParameter::Set(value) => ChannelInit::Fixed(value),
+		ColorParameter::Random => ChannelInit::Noise {
+			seed: seed.unwrap_or_else(|| rng.next_u32() as i32),
+		},
 	}
 }
 
 /// Argument group related to the noise
 #[derive(Debug, Clone, Copy, Args)]
 struct CliNoise {
-	/// Value that changes the output of the noise function
-	///
-	/// This value will be randomly choosen if not specified.
-	#[arg(long, value_name = "INT")]
-	seed: Option<i32>,
 	/// Number of cycles per unit length that the noise outputs
 	#[arg(long, value_name = "FLOAT")]
 	frequency: Option<f32>,
+	/// Number of noise layers to sum together into a fractal sum
+	#[arg(long, value_name = "INT", default_value_t = 1)]
+	octaves: u32,
+	/// Factor by which the frequency is multiplied at each successive octave
+	#[arg(long, value_name = "FLOAT", default_value_t = 2.0)]
+	lacunarity: f32,
+	/// Factor by which the amplitude is multiplied at each successive octave, a.k.a. gain
+	#[arg(long, value_name = "FLOAT", default_value_t = 0.5)]
+	persistence: f32,
+	/// Fold each octave through its absolute value (turbulence) instead of summing it as-is (fractal Brownian motion)
+	#[arg(long)]
+	turbulence: bool,
 }
 impl From<CliNoise> for NoiseOptions {
 	#[inline]
 	fn from(cli: CliNoise) -> Self {
 		Self {
-			seed: cli.seed.unwrap_or_else(rand::random),
 			frequency: cli.frequency.unwrap(),
+			octaves: cli.octaves,
+			lacunarity: cli.lacunarity,
+			persistence: cli.persistence,
+			turbulence: cli.turbulence,
 		}
 	}
 }
@@ -196,13 +363,22 @@ fn main() {
 		(f64::from(magnitude) as f32).recip()
 	});
 
-	let pixel_init = PixelInit::from(cli.color);
+	let seed = cli.seed.unwrap_or_else(rand::random);
+	let mut rng = ChaCha8Rng::seed_from_u64(seed);
+
+	let saturation = cli
+		.color
+		.saturation
+		.unwrap_or(ColorParameter::Set(cli.color_model.default_saturation()));
+	let brightness = cli
+		.color
+		.brightness
+		.unwrap_or(ColorParameter::Set(cli.color_model.default_brightness()));
+
+	let pixel_init = pixel_init_from_cli(cli.color, saturation, brightness, &mut rng);
 	let noise_options = NoiseOptions::from(cli.noise);
+	let color_model = ColorModel::from(cli.color_model);
 
-	println!(
-		"Generating '{}' with the following parameters:",
-		cli.output.display()
-	);
 	/// Prints each argument passed by CLI
 	macro_rules! print_args {
 		($( $key:tt = $value:expr ),* $(,)?) => {
@@ -212,17 +388,71 @@ fn main() {
 		};
 	}
 	print_args! {
+		seed = seed,
 		size = cli.size,
+		color_model = cli.color_model,
 		hue = cli.color.hue,
-		saturation = cli.color.saturation,
-		brightness = cli.color.brightness,
-		seed = noise_options.seed,
+		saturation = saturation,
+		brightness = brightness,
 		frequency = noise_options.frequency,
+		octaves = noise_options.octaves,
+		lacunarity = noise_options.lacunarity,
+		persistence = noise_options.persistence,
+		turbulence = noise_options.turbulence,
 	};
+	for (channel, init) in [
+		("hue", pixel_init.hue),
+		("saturation", pixel_init.saturation),
+		("brightness", pixel_init.brightness),
+	] {
+		if let ChannelInit::Noise { seed } = init {
+			println!("\t--{channel}-seed={seed}");
+		}
+	}
+
+	match cli.output {
+		CliOutput::File { path, depth } => {
+			println!("Generating '{}'", path.display());
 
-	let image =
-		random_gradient_generator::generate_image(cli.size, pixel_init, noise_options).unwrap();
-	image.save(&cli.output).unwrap();
+			let image = random_gradient_generator::generate_image(cli.size, pixel_init, noise_options, color_model)
+				.unwrap();
+			image.save(&path, depth.into()).unwrap();
+		}
+		CliOutput::Stream(stream) => {
+			println!("Streaming {} frames to '{}'", stream.frames, stream.remote);
+			print_args! {
+				frame_rate = stream.frame_rate,
+				frames = stream.frames,
+				time_step = stream.time_step,
+				serpentine = stream.serpentine,
+			};
+
+			let frames = random_gradient_generator::generate_animation(
+				cli.size,
+				pixel_init,
+				noise_options,
+				stream.frames,
+				stream.time_step,
+				color_model,
+			)
+			.unwrap();
+			let options = StreamOptions {
+				remote: stream.remote,
+				frame_rate: stream.frame_rate,
+				panel: PanelGeometry {
+					width: cli.size.width,
+					height: cli.size.height,
+					layout: if stream.serpentine {
+						PanelLayout::Serpentine
+					} else {
+						PanelLayout::RowMajor
+					},
+				},
+				response_timeout: stream.response_timeout,
+			};
+			random_gradient_generator::led::stream_frames(frames, options).unwrap();
+		}
+	}
 }
 
 #[cfg(test)]
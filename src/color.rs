@@ -0,0 +1,281 @@
+//! Color models a gradient's three [`PixelInit`](crate::PixelInit) components can be interpreted in
+
+use crate::{raster::Pixel16, OutOfRangeValue, HUE_RANGE, UNIT_RANGE};
+use std::ops::RangeInclusive;
+
+/// Valid range of an OKLab `a`/`b` chroma component
+///
+/// sRGB gamut colors fall roughly within this range; [`oklab_to_rgb`] clamps rather than rejects `a`/`b` pairs
+/// that land outside the sRGB gamut despite being individually within range.
+const OKLAB_CHROMA_RANGE: RangeInclusive<f32> = -0.4..=0.4;
+
+/// Color model a gradient's three [`PixelInit`](crate::PixelInit) components are interpreted in
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ColorModel {
+	/// Hue, saturation, brightness (a.k.a. value)
+	#[default]
+	Hsv,
+	/// Hue, saturation, lightness
+	Hsl,
+	/// OKLab `a`/`b` chroma, and lightness
+	///
+	/// Ramping only the lightness component through OKLab's perceptually uniform lightness axis, while `a`/`b`
+	/// chroma stay fixed, avoids the uneven visual steps that ramping HSV value or HSL lightness produces.
+	Oklab,
+}
+impl ColorModel {
+	/// Valid ranges of this model's three components, in the same order as
+	/// [`PixelInit`](crate::PixelInit)'s `hue`, `saturation` and `brightness` fields
+	#[must_use]
+	pub const fn ranges(self) -> [RangeInclusive<f32>; 3] {
+		match self {
+			Self::Hsv | Self::Hsl => [HUE_RANGE, UNIT_RANGE, UNIT_RANGE],
+			Self::Oklab => [OKLAB_CHROMA_RANGE, OKLAB_CHROMA_RANGE, UNIT_RANGE],
+		}
+	}
+}
+impl ToRgb for ColorModel {
+	fn to_rgb(self, hue: f32, saturation: f32, brightness: f32) -> Result<Pixel16, OutOfRangeValue> {
+		match self {
+			Self::Hsv => hsv_to_rgb(hue, saturation, brightness),
+			Self::Hsl => hsl_to_rgb(hue, saturation, brightness),
+			Self::Oklab => oklab_to_rgb(hue, saturation, brightness),
+		}
+	}
+}
+
+/// Converts a [`ColorModel`]'s three already range-mapped components into an RGB pixel
+///
+/// Implemented by [`ColorModel`] so the crate's internal pixel-mapping step can convert through whichever model
+/// was selected without matching on it itself.
+pub trait ToRgb {
+	/// Converts `hue`, `saturation` and `brightness` into an RGB pixel
+	///
+	/// Despite the parameter names, which [`ColorModel`] `self` is decides what these three components actually
+	/// represent (see [`ColorModel::ranges`]).
+	///
+	/// # Errors
+	/// Returns an [`OutOfRangeValue`] if `hue`, `saturation` or `brightness` falls outside this model's
+	/// [`ranges`](ColorModel::ranges).
+	fn to_rgb(self, hue: f32, saturation: f32, brightness: f32) -> Result<Pixel16, OutOfRangeValue>;
+}
+
+/// Converts HSV to RGB
+///
+/// <math display="block">
+///   <mi>C</mi>
+///   <mo>=</mo>
+///   <mi>saturation</mi>
+///   <mo>×</mo>
+///   <mi>brightness</mi>
+/// </math>
+/// `(red', green', blue')` is then read off the hue-quadrant table in [`hue_quadrant`], and:
+/// <math display="block">
+///   <mi>M</mi>
+///   <mo>=</mo>
+///   <mi>brightness</mi>
+///   <mo>-</mo>
+///   <mi>C</mi>
+/// </math>
+/// <math display="block">
+///   <mrow><mo>(</mo><mrow>
+///   <mi>red</mi>
+///   <mo>,</mo>
+///   <mi>green</mi>
+///   <mo>,</mo>
+///   <mi>blue</mi>
+///   </mrow><mo>)</mo></mrow>
+///   <mo>=</mo>
+///   <mrow><mo>(</mo><mrow>
+///   <mrow><mo>(</mo><mrow>
+///   <mi>red'</mi><mo>+</mo><mi>M</mi>
+///   </mrow><mo>)</mo></mrow>
+///   <mo>×</mo><mn>65535</mn>
+///   <mo>,</mo>
+///   <mrow><mo>(</mo><mrow>
+///   <mi>green'</mi><mo>+</mo><mi>M</mi>
+///   </mrow><mo>)</mo></mrow>
+///   <mo>×</mo><mn>65535</mn>
+///   <mo>,</mo>
+///   <mrow><mo>(</mo><mrow>
+///   <mi>blue'</mi><mo>+</mo><mi>M</mi>
+///   </mrow><mo>)</mo></mrow>
+///   <mo>×</mo><mn>65535</mn>
+///   </mrow><mo>)</mo></mrow>
+/// </math>
+///
+/// # Errors
+/// This function will return an [`OutOfRangeValue`] error if any of the following conditions is false:
+/// - <math><mn>0</mn><mo>≤</mo><mi>hue</mi><mo><</mo><mn>360</mn></math>
+/// - <math><mn>0</mn><mo>≤</mo><mi>saturation</mi><mo>≤</mo><mn>1</mn></math>
+/// - <math><mn>0</mn><mo>≤</mo><mi>brightness</mi><mo>≤</mo><mn>1</mn></math>
+pub fn hsv_to_rgb(hue: f32, saturation: f32, brightness: f32) -> Result<Pixel16, OutOfRangeValue> {
+	if !(0.0..360.0).contains(&hue) {
+		return Err(OutOfRangeValue::Hue);
+	}
+	if !UNIT_RANGE.contains(&saturation) {
+		return Err(OutOfRangeValue::Saturation);
+	}
+	if !UNIT_RANGE.contains(&brightness) {
+		return Err(OutOfRangeValue::Brightness);
+	}
+
+	let c = saturation * brightness;
+	let (r, g, b) = hue_quadrant(hue, c);
+	let m = brightness - c;
+	Ok(to_pixel16(r, g, b, m))
+}
+
+/// Converts HSL to RGB
+///
+/// Shares [`hsv_to_rgb`]'s `(red', green', blue')` hue-quadrant table (see [`hue_quadrant`]) and final
+/// <math><mo>(</mo><mi>red'</mi><mo>+</mo><mi>M</mi><mo>)</mo><mo>×</mo><mn>65535</mn></math> scaling, but derives
+/// chroma and the match value from lightness instead of HSV's value/brightness:
+/// <math display="block">
+///   <mi>C</mi>
+///   <mo>=</mo>
+///   <mrow><mo>(</mo><mrow>
+///   <mn>1</mn>
+///   <mo>-</mo>
+///   <mrow><mo>|</mo><mrow>
+///   <mn>2</mn>
+///   <mo>×</mo>
+///   <mi>lightness</mi>
+///   <mo>-</mo>
+///   <mn>1</mn>
+///   </mrow><mo>|</mo></mrow>
+///   </mrow><mo>)</mo></mrow>
+///   <mo>×</mo>
+///   <mi>saturation</mi>
+/// </math>
+/// <math display="block">
+///   <mi>M</mi>
+///   <mo>=</mo>
+///   <mi>lightness</mi>
+///   <mo>-</mo>
+///   <mfrac><mi>C</mi><mn>2</mn></mfrac>
+/// </math>
+///
+/// # Errors
+/// This function will return an [`OutOfRangeValue`] error if any of the following conditions is false:
+/// - <math><mn>0</mn><mo>≤</mo><mi>hue</mi><mo><</mo><mn>360</mn></math>
+/// - <math><mn>0</mn><mo>≤</mo><mi>saturation</mi><mo>≤</mo><mn>1</mn></math>
+/// - <math><mn>0</mn><mo>≤</mo><mi>lightness</mi><mo>≤</mo><mn>1</mn></math>
+pub fn hsl_to_rgb(hue: f32, saturation: f32, lightness: f32) -> Result<Pixel16, OutOfRangeValue> {
+	if !(0.0..360.0).contains(&hue) {
+		return Err(OutOfRangeValue::Hue);
+	}
+	if !UNIT_RANGE.contains(&saturation) {
+		return Err(OutOfRangeValue::Saturation);
+	}
+	if !UNIT_RANGE.contains(&lightness) {
+		return Err(OutOfRangeValue::Lightness);
+	}
+
+	let c = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+	let (r, g, b) = hue_quadrant(hue, c);
+	let m = lightness - c / 2.0;
+	Ok(to_pixel16(r, g, b, m))
+}
+
+/// Splits `hue` into the `(red', green', blue')` triple shared by [`hsv_to_rgb`] and [`hsl_to_rgb`], given their
+/// common chroma `c`
+fn hue_quadrant(hue: f32, c: f32) -> (f32, f32, f32) {
+	let x = c * (1.0 - ((hue / 60.0) % 2.0 - 1.0).abs());
+	if hue < 60.0 {
+		(c, x, 0.0)
+	} else if hue < 120.0 {
+		(x, c, 0.0)
+	} else if hue < 180.0 {
+		(0.0, c, x)
+	} else if hue < 240.0 {
+		(0.0, x, c)
+	} else if hue < 300.0 {
+		(x, 0.0, c)
+	} else {
+		(c, 0.0, x)
+	}
+}
+
+/// Shifts `(r, g, b)` by the match value `m` and scales the result into a 16-bit-per-channel [`Pixel16`]
+fn to_pixel16(r: f32, g: f32, b: f32, m: f32) -> Pixel16 {
+	Pixel16 {
+		r: ((r + m) * 65535.0) as u16,
+		g: ((g + m) * 65535.0) as u16,
+		b: ((b + m) * 65535.0) as u16,
+	}
+}
+
+/// Converts an OKLab color to RGB
+///
+/// Uses Björn Ottosson's published OKLab → linear sRGB matrices, then gamma-encodes the result through the sRGB
+/// transfer function. `a`/`b` pairs that land outside the sRGB gamut despite being individually in range are
+/// clamped rather than rejected.
+///
+/// # Errors
+/// This function will return an [`OutOfRangeValue`] error if any of the following conditions is false:
+/// - <math><mn>-0.4</mn><mo>≤</mo><mi>a</mi><mo>≤</mo><mn>0.4</mn></math>
+/// - <math><mn>-0.4</mn><mo>≤</mo><mi>b</mi><mo>≤</mo><mn>0.4</mn></math>
+/// - <math><mn>0</mn><mo>≤</mo><mi>lightness</mi><mo>≤</mo><mn>1</mn></math>
+pub fn oklab_to_rgb(a: f32, b: f32, lightness: f32) -> Result<Pixel16, OutOfRangeValue> {
+	if !OKLAB_CHROMA_RANGE.contains(&a) || !OKLAB_CHROMA_RANGE.contains(&b) {
+		return Err(OutOfRangeValue::OklabChroma);
+	}
+	if !UNIT_RANGE.contains(&lightness) {
+		return Err(OutOfRangeValue::Lightness);
+	}
+
+	let l_ = lightness + 0.396_337_78 * a + 0.215_803_76 * b;
+	let m_ = lightness - 0.105_561_346 * a - 0.063_854_17 * b;
+	let s_ = lightness - 0.089_484_18 * a - 1.291_485_5 * b;
+
+	let l = l_.powi(3);
+	let m = m_.powi(3);
+	let s = s_.powi(3);
+
+	let r = 4.076_741_7 * l - 3.307_711_6 * m + 0.230_969_93 * s;
+	let g = -1.268_438 * l + 2.609_757_4 * m - 0.341_319_4 * s;
+	let blue = -0.004_196_086_3 * l - 0.703_418_6 * m + 1.707_614_7 * s;
+
+	Ok(Pixel16 {
+		r: gamma_encode(r),
+		g: gamma_encode(g),
+		b: gamma_encode(blue),
+	})
+}
+
+/// Gamma-encodes a linear sRGB channel value through the sRGB transfer function and scales it to 16-bit range,
+/// clamping out-of-gamut values into <math><mo>[</mo><mn>0</mn><mo>,</mo><mn>1</mn><mo>]</mo></math> first
+fn gamma_encode(linear: f32) -> u16 {
+	let linear = linear.clamp(0.0, 1.0);
+	let encoded = if linear <= 0.003_130_8 {
+		12.92 * linear
+	} else {
+		1.055 * linear.powf(1.0 / 2.4) - 0.055
+	};
+	(encoded * 65535.0).round() as u16
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn hue_quadrant_boundaries() {
+		assert_eq!(hue_quadrant(0.0, 1.0), (1.0, 0.0, 0.0));
+		assert_eq!(hue_quadrant(60.0, 1.0), (1.0, 1.0, 0.0));
+		assert_eq!(hue_quadrant(120.0, 1.0), (0.0, 1.0, 0.0));
+		assert_eq!(hue_quadrant(180.0, 1.0), (0.0, 1.0, 1.0));
+		assert_eq!(hue_quadrant(240.0, 1.0), (0.0, 0.0, 1.0));
+		assert_eq!(hue_quadrant(300.0, 1.0), (1.0, 0.0, 1.0));
+	}
+
+	#[test]
+	fn gamma_encode_boundaries() {
+		assert_eq!(gamma_encode(0.0), 0);
+		assert_eq!(gamma_encode(1.0), 65535);
+		// Out-of-gamut values are clamped into [0, 1] before encoding.
+		assert_eq!(gamma_encode(-1.0), 0);
+		assert_eq!(gamma_encode(2.0), 65535);
+	}
+}
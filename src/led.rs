@@ -0,0 +1,217 @@
+//! Streaming generated frames over UDP to addressable LED matrices
+
+use crate::{
+	raster::{downcast, RasterImage},
+	OutOfRangeValue,
+};
+use std::{
+	error::Error,
+	fmt::{self, Display, Formatter},
+	io,
+	net::{Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket},
+	thread,
+	time::{Duration, Instant},
+};
+
+/// Wiring layout of an LED panel's pixels
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PanelLayout {
+	/// Every row is wired in the same direction, left-to-right, like a raster image
+	#[default]
+	RowMajor,
+	/// Rows alternate direction, every other row wired right-to-left
+	Serpentine,
+}
+
+/// Geometry of a physical LED panel addressed by a [`PanelLayout`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PanelGeometry {
+	/// Panel width in pixels
+	pub width: u32,
+	/// Panel height in pixels
+	pub height: u32,
+	/// Wiring layout of the panel's pixels
+	pub layout: PanelLayout,
+}
+impl PanelGeometry {
+	/// Serializes `image`'s pixels into a raw RGB frame buffer laid out according to `self`
+	///
+	/// # Panics
+	/// This function will panic if a panel coordinate falls outside `image`'s pixel buffer (see
+	/// [`RasterImage::get_pixel`]). `image`'s dimensions are not otherwise checked against
+	/// [`self.width`](Self::width) and [`self.height`](Self::height).
+	#[must_use]
+	pub fn frame_bytes(&self, image: &RasterImage) -> Vec<u8> {
+		let mut bytes = Vec::with_capacity(self.width as usize * self.height as usize * 3);
+		for y in 0..self.height {
+			let reversed = self.layout == PanelLayout::Serpentine && y % 2 == 1;
+			for col in 0..self.width {
+				let x = if reversed { self.width - 1 - col } else { col };
+				let px = image.get_pixel(x, y);
+				bytes.extend_from_slice(&[downcast(px.r), downcast(px.g), downcast(px.b)]);
+			}
+		}
+
+		bytes
+	}
+}
+
+/// Parameters to stream frames with [`stream_frames`]
+#[derive(Debug, Clone, Copy)]
+pub struct StreamOptions {
+	/// Address of the remote receiver
+	pub remote: SocketAddr,
+	/// Number of frames sent per second
+	pub frame_rate: f32,
+	/// Geometry of the target panel, used to serialize each frame
+	pub panel: PanelGeometry,
+	/// How long to wait, in seconds, for a per-frame completion response before giving up, if any
+	pub response_timeout: Option<f32>,
+}
+
+/// Streams `frames` to [`options.remote`](StreamOptions::remote) over UDP,
+/// pacing them at [`options.frame_rate`](StreamOptions::frame_rate)
+///
+/// # Errors
+/// This function will return a [`StreamError::InvalidFrameRate`] or [`StreamError::InvalidResponseTimeout`] if
+/// [`options.frame_rate`](StreamOptions::frame_rate) is not finite and strictly positive, or
+/// [`options.response_timeout`](StreamOptions::response_timeout) is set but not finite and strictly positive; a
+/// [`StreamError::Frame`] if any of `frames` is itself an error; and a [`StreamError::Io`] if the socket cannot be
+/// bound or a frame (or its completion response, when `options.response_timeout` is set) cannot be exchanged.
+pub fn stream_frames(
+	frames: impl Iterator<Item = Result<RasterImage, OutOfRangeValue>>,
+	options: StreamOptions,
+) -> Result<(), StreamError> {
+	if !(options.frame_rate.is_finite() && options.frame_rate > 0.0) {
+		return Err(StreamError::InvalidFrameRate(options.frame_rate));
+	}
+	if let Some(timeout) = options.response_timeout {
+		if !(timeout.is_finite() && timeout > 0.0) {
+			return Err(StreamError::InvalidResponseTimeout(timeout));
+		}
+	}
+
+	let socket = if options.remote.is_ipv6() {
+		UdpSocket::bind((Ipv6Addr::UNSPECIFIED, 0))?
+	} else {
+		UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?
+	};
+	socket.connect(options.remote)?;
+	socket.set_read_timeout(options.response_timeout.map(Duration::from_secs_f32))?;
+
+	let frame_period = Duration::from_secs_f32(options.frame_rate.recip());
+	for frame in frames {
+		let started_at = Instant::now();
+
+		let bytes = options.panel.frame_bytes(&frame?);
+		socket.send(&bytes)?;
+		if options.response_timeout.is_some() {
+			let mut response = [0; 1];
+			socket.recv(&mut response)?;
+		}
+
+		if let Some(remaining) = frame_period.checked_sub(started_at.elapsed()) {
+			thread::sleep(remaining);
+		}
+	}
+
+	Ok(())
+}
+
+/// Error returned by [`stream_frames`]
+#[derive(Debug)]
+pub enum StreamError {
+	/// A frame failed to generate
+	Frame(OutOfRangeValue),
+	/// The underlying UDP socket failed
+	Io(io::Error),
+	/// [`StreamOptions::frame_rate`] was not a finite, strictly positive number of frames per second
+	InvalidFrameRate(f32),
+	/// [`StreamOptions::response_timeout`] was set but not a finite, strictly positive number of seconds
+	InvalidResponseTimeout(f32),
+}
+impl Display for StreamError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Frame(err) => Display::fmt(err, f),
+			Self::Io(err) => Display::fmt(err, f),
+			Self::InvalidFrameRate(rate) => {
+				write!(f, "frame rate must be a finite, positive number of frames per second (got {rate})")
+			}
+			Self::InvalidResponseTimeout(timeout) => {
+				write!(f, "response timeout must be a finite, strictly positive number of seconds (got {timeout})")
+			}
+		}
+	}
+}
+impl Error for StreamError {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
+		match self {
+			Self::Frame(err) => Some(err),
+			Self::Io(err) => Some(err),
+			Self::InvalidFrameRate(_) | Self::InvalidResponseTimeout(_) => None,
+		}
+	}
+}
+impl From<io::Error> for StreamError {
+	#[inline]
+	fn from(err: io::Error) -> Self {
+		Self::Io(err)
+	}
+}
+impl From<OutOfRangeValue> for StreamError {
+	#[inline]
+	fn from(err: OutOfRangeValue) -> Self {
+		Self::Frame(err)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::raster::Pixel16;
+
+	/// Builds a 2×2 image whose pixels' red channel is numbered `0..4` in row-major order, high byte only so
+	/// [`downcast`](crate::raster::downcast) round-trips it unchanged
+	fn test_image() -> RasterImage {
+		let mut image = RasterImage::new(2, 2);
+		for (i, (x, y)) in image.coordinates().enumerate() {
+			image.set_pixel(
+				x,
+				y,
+				Pixel16 {
+					r: (i as u16) << 8,
+					..Pixel16::default()
+				},
+			);
+		}
+		image
+	}
+
+	#[test]
+	fn frame_bytes_row_major() {
+		let geometry = PanelGeometry {
+			width: 2,
+			height: 2,
+			layout: PanelLayout::RowMajor,
+		};
+		assert_eq!(
+			geometry.frame_bytes(&test_image()),
+			vec![0, 0, 0, 1, 0, 0, 2, 0, 0, 3, 0, 0]
+		);
+	}
+
+	#[test]
+	fn frame_bytes_serpentine() {
+		let geometry = PanelGeometry {
+			width: 2,
+			height: 2,
+			layout: PanelLayout::Serpentine,
+		};
+		// Row 0 (even) stays left-to-right, row 1 (odd) is reversed.
+		assert_eq!(
+			geometry.frame_bytes(&test_image()),
+			vec![0, 0, 0, 1, 0, 0, 3, 0, 0, 2, 0, 0]
+		);
+	}
+}
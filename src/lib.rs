@@ -48,7 +48,12 @@
 )]
 #![forbid(unsafe_code, clippy::missing_panics_doc, clippy::missing_errors_doc)]
 
-use bmp::{Image, Pixel};
+pub mod color;
+pub mod led;
+pub mod raster;
+
+use color::{ColorModel, ToRgb};
+use raster::RasterImage;
 use std::{
 	error::Error,
 	fmt::{self, Display, Formatter},
@@ -96,73 +101,208 @@ impl Display for Size {
 	}
 }
 
-#[allow(missing_docs, clippy::missing_docs_in_private_items)]
+/// Initial configuration of a single [`PixelInit`] channel
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChannelInit {
+	/// The channel is fixed at this value for every pixel
+	Fixed(f32),
+	/// The channel is driven by its own noise field, seeded independently from the other channels
+	/// so that multiple randomized channels decorrelate instead of moving in lockstep
+	Noise {
+		/// Value that changes the output of this channel's noise
+		seed: i32,
+	},
+}
+
 /// Initial components of the pixel colors
 ///
-/// Each variant represents the component that will be randomized
-/// and stores the two others.
+/// Each component is independently either [fixed](ChannelInit::Fixed) or [noise-driven](ChannelInit::Noise). What
+/// the three fields actually represent depends on the [`ColorModel`] passed to [`generate_image`]/
+/// [`generate_animation`]; see each field's doc for its meaning under every model, and [`ColorModel::ranges`] for
+/// its valid range.
 #[derive(Debug, Clone, Copy, PartialEq)]
-pub enum PixelInit {
-	/// Randomize `hue`
-	Hue { saturation: f32, brightness: f32 },
-	/// Randomize `saturation`
-	Saturation { hue: f32, brightness: f32 },
-	/// Randomize `brightness`
-	Brightness { hue: f32, saturation: f32 },
-}
-impl PixelInit {
-	/// Returns the valid range for the randomzed component
-	#[inline]
-	pub const fn valid_range(&self) -> RangeInclusive<f32> {
-		match self {
-			Self::Hue { .. } => 0.0..=359.99,
-			Self::Saturation { .. } => 0.0..=1.0,
-			Self::Brightness { .. } => 0.0..=1.0,
-		}
-	}
+pub struct PixelInit {
+	/// Hue, for [`ColorModel::Hsv`]/[`ColorModel::Hsl`]; OKLab `a` chroma, for [`ColorModel::Oklab`]
+	pub hue: ChannelInit,
+	/// Saturation, for [`ColorModel::Hsv`]/[`ColorModel::Hsl`]; OKLab `b` chroma, for [`ColorModel::Oklab`]
+	pub saturation: ChannelInit,
+	/// Brightness (HSV value) or lightness (HSL/OKLab `L`), depending on the [`ColorModel`]
+	pub brightness: ChannelInit,
 }
 
+/// Valid range of a hue component, in degrees
+const HUE_RANGE: RangeInclusive<f32> = 0.0..=359.99;
+/// Valid range of a normalized (<math><mo>[</mo><mn>0</mn><mo>,</mo><mn>1</mn><mo>]</mo></math>) component
+const UNIT_RANGE: RangeInclusive<f32> = 0.0..=1.0;
+
 /// Parameters to construct the noise with
 #[derive(Debug, Default, Clone, Copy, PartialEq)]
 pub struct NoiseOptions {
-	/// Value that changes the output of the noise
-	pub seed: i32,
 	/// Number of cycles per unit length that the noise outputs
 	pub frequency: f32,
+	/// Number of noise layers summed together into a fractal sum
+	///
+	/// A value of `1` reproduces a plain, single-octave gradient noise. Must be at least `1`.
+	pub octaves: u32,
+	/// Factor by which [`frequency`](Self::frequency) is multiplied at each successive octave (typically `2.0`)
+	pub lacunarity: f32,
+	/// Factor by which the amplitude is multiplied at each successive octave (typically `0.5`), a.k.a. gain
+	pub persistence: f32,
+	/// Whether each octave is folded through [`f32::abs`] before being summed (turbulence), instead of being summed as-is (fractal Brownian motion)
+	pub turbulence: bool,
 }
 
-/// Generates an image with given `size`, `pixel_init` and `noise_options`
+/// Generates an image with given `size`, `pixel_init` and `noise_options`, in `color_model`
+///
+/// Each of `pixel_init`'s channels that is [`ChannelInit::Noise`] gets its own fractal-sum noise field (see
+/// [`NoiseOptions`]), seeded independently, so that e.g. `pixel_init.hue` and `pixel_init.brightness` can both vary
+/// across the image without moving in lockstep.
 ///
 /// # Errors
-/// This function will return an [`OutOfRangeValue`] error if any of the following conditions is false:
-/// - <math><mn>0</mn><mo>≤</mo><mi>hue</mi><mo><</mo><mn>360</mn></math>
-/// - <math><mn>0</mn><mo>≤</mo><mi>saturation</mi><mo>≤</mo><mn>1</mn></math>
-/// - <math><mn>0</mn><mo>≤</mo><mi>brightness</mi><mo>≤</mo><mn>1</mn></math>
+/// This function will return an [`OutOfRangeValue::Octaves`] error if
+/// [`noise_options.octaves`](NoiseOptions::octaves) is `0`, and an [`OutOfRangeValue`] error if any of
+/// `pixel_init`'s three components falls outside the range `color_model` expects for it (see
+/// [`ColorModel::ranges`]).
 pub fn generate_image(
 	size: Size,
 	pixel_init: PixelInit,
 	noise_options: NoiseOptions,
-) -> Result<Image, OutOfRangeValue> {
-	use simdnoise::NoiseBuilder;
+	color_model: ColorModel,
+) -> Result<RasterImage, OutOfRangeValue> {
+	if noise_options.octaves < 1 {
+		return Err(OutOfRangeValue::Octaves);
+	}
 
-	let mut settings = NoiseBuilder::gradient_2d(size.width as usize, size.height as usize);
-	settings
-		.with_freq(noise_options.frequency)
-		.with_seed(noise_options.seed);
-	let noise_range = pixel_init.valid_range();
-	let noise = settings.generate_scaled(*noise_range.start(), *noise_range.end());
+	let [hue_range, saturation_range, brightness_range] = color_model.ranges();
+	let channels = [
+		ChannelField::new_2d(size, noise_options, pixel_init.hue, hue_range),
+		ChannelField::new_2d(size, noise_options, pixel_init.saturation, saturation_range),
+		ChannelField::new_2d(size, noise_options, pixel_init.brightness, brightness_range),
+	];
+	image_from_channels(size, &channels, 0, color_model)
+}
+
+/// Generates an animation of `frames` images, of given `size`, `pixel_init` and `noise_options`, in `color_model`
+///
+/// Each noise-driven channel's field is sampled along a third, time axis using
+/// [`NoiseBuilder::gradient_3d`](simdnoise::NoiseBuilder::gradient_3d): frame `n` samples the same x/y plane as
+/// [`generate_image`] at <math><mi>z</mi><mo>=</mo><mi>n</mi><mo>×</mo><mi>time_step</mi></math>, so consecutive
+/// frames evolve coherently instead of flickering between unrelated fields.
+///
+/// # Errors
+/// This function will return an [`OutOfRangeValue::Octaves`] error immediately if
+/// [`noise_options.octaves`](NoiseOptions::octaves) is `0`. Otherwise, each yielded [`RasterImage`] carries the
+/// same error conditions as [`generate_image`].
+pub fn generate_animation(
+	size: Size,
+	pixel_init: PixelInit,
+	noise_options: NoiseOptions,
+	frames: u32,
+	time_step: f32,
+	color_model: ColorModel,
+) -> Result<impl Iterator<Item = Result<RasterImage, OutOfRangeValue>>, OutOfRangeValue> {
+	if noise_options.octaves < 1 {
+		return Err(OutOfRangeValue::Octaves);
+	}
 
-	let mut image = Image::new(size.width, size.height);
+	let [hue_range, saturation_range, brightness_range] = color_model.ranges();
+	let channels = [
+		ChannelField::new_3d(size, noise_options, pixel_init.hue, hue_range, frames, time_step),
+		ChannelField::new_3d(
+			size,
+			noise_options,
+			pixel_init.saturation,
+			saturation_range,
+			frames,
+			time_step,
+		),
+		ChannelField::new_3d(
+			size,
+			noise_options,
+			pixel_init.brightness,
+			brightness_range,
+			frames,
+			time_step,
+		),
+	];
+	let plane_len = (size.width * size.height) as usize;
+
+	Ok((0..frames as usize).map(move |frame| image_from_channels(size, &channels, frame * plane_len, color_model)))
+}
+
+/// Per-pixel values of a single [`PixelInit`] channel: either constant, or sampled from a pre-computed,
+/// <math><mo>[</mo><mn>0</mn><mo>,</mo><mn>1</mn><mo>]</mo></math>-normalized noise field and mapped into `range`
+#[derive(Debug, Clone)]
+enum ChannelField {
+	/// The channel is fixed at this value for every pixel
+	Fixed(f32),
+	/// The channel is sampled from `field` and mapped into `range`
+	Noise {
+		/// Normalized noise field, one value per pixel (or, for an animation, per pixel and frame)
+		field: Vec<f32>,
+		/// Range the normalized noise is mapped into
+		range: RangeInclusive<f32>,
+	},
+}
+impl ChannelField {
+	/// Builds the channel field for a single 2D image
+	fn new_2d(size: Size, noise_options: NoiseOptions, init: ChannelInit, range: RangeInclusive<f32>) -> Self {
+		match init {
+			ChannelInit::Fixed(value) => Self::Fixed(value),
+			ChannelInit::Noise { seed } => Self::Noise {
+				field: fractal_sum_2d(size, noise_options, seed),
+				range,
+			},
+		}
+	}
+
+	/// Builds the channel field for an animation of `frames` images, `time_step` apart
+	fn new_3d(
+		size: Size,
+		noise_options: NoiseOptions,
+		init: ChannelInit,
+		range: RangeInclusive<f32>,
+		frames: u32,
+		time_step: f32,
+	) -> Self {
+		match init {
+			ChannelInit::Fixed(value) => Self::Fixed(value),
+			ChannelInit::Noise { seed } => Self::Noise {
+				field: fractal_sum_3d(size, noise_options, seed, frames, time_step),
+				range,
+			},
+		}
+	}
+
+	/// Returns the channel's value for the pixel at `index`
+	fn value_at(&self, index: usize) -> f32 {
+		match self {
+			Self::Fixed(value) => *value,
+			Self::Noise { field, range } => {
+				let unit = field[index];
+				*range.start() + unit * (range.end() - range.start())
+			}
+		}
+	}
+}
+
+/// Maps `channels` onto a [`RasterImage`] through `color_model`, starting at `plane_offset` into each channel's field
+fn image_from_channels(
+	size: Size,
+	channels: &[ChannelField; 3],
+	plane_offset: usize,
+	color_model: ColorModel,
+) -> Result<RasterImage, OutOfRangeValue> {
+	let [hue, saturation, brightness] = channels;
+
+	let mut image = RasterImage::new(size.width, size.height);
 	for (x, y) in image.coordinates() {
-		let noise_value = noise[(size.width * y + x) as usize];
-		let px = match pixel_init {
-			PixelInit::Hue {
-				saturation,
-				brightness,
-			} => hsv_to_rgb(noise_value, saturation, brightness),
-			PixelInit::Saturation { hue, brightness } => hsv_to_rgb(hue, noise_value, brightness),
-			PixelInit::Brightness { hue, saturation } => hsv_to_rgb(hue, saturation, noise_value),
-		}?;
+		let index = plane_offset + (size.width * y + x) as usize;
+		let px = color_model.to_rgb(
+			hue.value_at(index),
+			saturation.value_at(index),
+			brightness.value_at(index),
+		)?;
 
 		image.set_pixel(x, y, px);
 	}
@@ -170,232 +310,72 @@ pub fn generate_image(
 	Ok(image)
 }
 
-/// Converts HSV to RGB
-///
-/// <math display="block">
-///   <mi>C</mi>
-///   <mo>=</mo>
-///   <mi>saturation</mi>
-///   <mo>×</mo>
-///   <mi>brightness</mi>
-/// </math>
-/// <math display="block">
-///   <mi>X</mi>
-///   <mo>=</mo>
-///   <mi>C</mi>
-///   <mo>×</mo>
-///   <mrow><mo>(</mo><mrow>
-///   <mn>1</mn>
-///   <mo>-</mo>
-///   <mrow><mo>|</mo><mrow>
-///   <mfrac>
-///     <mi>hue</mi>
-///     <mn>60</mn>
-///   </mfrac>
-///   <mo>%</mo>
-///   <mn>2</mn>
-///   <mo>-</mo>
-///   <mn>1</mn>
-///   </mrow><mo>|</mo></mrow>
-///   </mrow><mo>)</mo></mrow>
-/// </math>
-/// <math display="block">
-///   <mi>M</mi>
-///   <mo>=</mo>
-///   <mi>brightness</mi>
-///   <mo>-</mo>
-///   <mi>C</mi>
-/// </math>
-/// <math display="block">
-///   <mrow><mo>(</mo><mrow>
-///   <mi>red'</mi>
-///   <mo>,</mo>
-///   <mi>green'</mi>
-///   <mo>,</mo>
-///   <mi>blue'</mi>
-///   </mrow><mo>)</mo></mrow>
-///   <mo>=</mo>
-///   <mrow><mo>{</mo><mtable>
-///     <mtr>
-///       <mtd><mo>(</mo><mrow>
-///       <mi>C</mi>
-///       <mo>,</mo>
-///       <mi>X</mi>
-///       <mo>,</mo>
-///       <mi>0</mi>
-///       </mrow><mo>)</mo></mtd>
-///       <mtd>
-///         <mtext>if</mtext>
-///         <mpadded lspace="1em">
-///           <mn>0</mn>
-///           <mo>≤</mo>
-///           <mi>hue</mi>
-///           <mo><</mo>
-///           <mn>60</mn>
-///         </mpadded>
-///       </mtd>
-///     </mtr>
-///     <mtr>
-///       <mtd><mo>(</mo><mrow>
-///       <mi>X</mi>
-///       <mo>,</mo>
-///       <mi>C</mi>
-///       <mo>,</mo>
-///       <mi>0</mi>
-///       </mrow><mo>)</mo></mtd>
-///       <mtd>
-///         <mtext>if</mtext>
-///         <mpadded lspace="1em">
-///           <mn>60</mn>
-///           <mo>≤</mo>
-///           <mi>hue</mi>
-///           <mo><</mo>
-///           <mn>120</mn>
-///         </mpadded>
-///       </mtd>
-///     </mtr>
-///     <mtr>
-///       <mtd><mo>(</mo><mrow>
-///       <mi>0</mi>
-///       <mo>,</mo>
-///       <mi>C</mi>
-///       <mo>,</mo>
-///       <mi>X</mi>
-///       </mrow><mo>)</mo></mtd>
-///       <mtd>
-///         <mtext>if</mtext>
-///         <mpadded lspace="1em">
-///           <mn>120</mn>
-///           <mo>≤</mo>
-///           <mi>hue</mi>
-///           <mo><</mo>
-///           <mn>180</mn>
-///         </mpadded>
-///       </mtd>
-///     </mtr>
-///     <mtr>
-///       <mtd><mo>(</mo><mrow>
-///       <mi>0</mi>
-///       <mo>,</mo>
-///       <mi>X</mi>
-///       <mo>,</mo>
-///       <mi>C</mi>
-///       </mrow><mo>)</mo></mtd>
-///       <mtd>
-///         <mtext>if</mtext>
-///         <mpadded lspace="1em">
-///           <mn>180</mn>
-///           <mo>≤</mo>
-///           <mi>hue</mi>
-///           <mo><</mo>
-///           <mn>240</mn>
-///         </mpadded>
-///       </mtd>
-///     </mtr>
-///     <mtr>
-///       <mtd><mo>(</mo><mrow>
-///       <mi>X</mi>
-///       <mo>,</mo>
-///       <mi>0</mi>
-///       <mo>,</mo>
-///       <mi>C</mi>
-///       </mrow><mo>)</mo></mtd>
-///       <mtd>
-///         <mtext>if</mtext>
-///         <mpadded lspace="1em">
-///           <mn>240</mn>
-///           <mo>≤</mo>
-///           <mi>hue</mi>
-///           <mo><</mo>
-///           <mn>300</mn>
-///         </mpadded>
-///       </mtd>
-///     </mtr>
-///     <mtr>
-///       <mtd><mo>(</mo><mrow>
-///       <mi>C</mi>
-///       <mo>,</mo>
-///       <mi>0</mi>
-///       <mo>,</mo>
-///       <mi>X</mi>
-///       </mrow><mo>)</mo></mtd>
-///       <mtd>
-///         <mtext>if</mtext>
-///         <mpadded lspace="1em">
-///           <mn>300</mn>
-///           <mo>≤</mo>
-///           <mi>hue</mi>
-///           <mo><</mo>
-///           <mn>360</mn>
-///         </mpadded>
-///       </mtd>
-///     </mtr>
-///   </mtable></mrow>
-/// </math>
-/// <math display="block">
-///   <mrow><mo>(</mo><mrow>
-///   <mi>red</mi>
-///   <mo>,</mo>
-///   <mi>green</mi>
-///   <mo>,</mo>
-///   <mi>blue</mi>
-///   </mrow><mo>)</mo></mrow>
-///   <mo>=</mo>
-///   <mrow><mo>(</mo><mrow>
-///   <mrow><mo>(</mo><mrow>
-///   <mi>red'</mi><mo>+</mo><mi>M</mi>
-///   </mrow><mo>)</mo></mrow>
-///   <mo>×</mo><mn>255</mn>
-///   <mo>,</mo>
-///   <mrow><mo>(</mo><mrow>
-///   <mi>green'</mi><mo>+</mo><mi>M</mi>
-///   </mrow><mo>)</mo></mrow>
-///   <mo>×</mo><mn>255</mn>
-///   <mo>,</mo>
-///   <mrow><mo>(</mo><mrow>
-///   <mi>blue'</mi><mo>+</mo><mi>M</mi>
-///   </mrow><mo>)</mo></mrow>
-///   <mo>×</mo><mn>255</mn>
-///   </mrow><mo>)</mo></mrow>
-/// </math>
+/// Computes the [fractal sum](NoiseOptions#structfield.octaves) of a 2D gradient noise field seeded with `seed`,
+/// normalized into <math><mo>[</mo><mn>0</mn><mo>,</mo><mn>1</mn><mo>]</mo></math>
+fn fractal_sum_2d(size: Size, noise_options: NoiseOptions, seed: i32) -> Vec<f32> {
+	use simdnoise::NoiseBuilder;
+
+	let (width, height) = (size.width as usize, size.height as usize);
+	accumulate_octaves(noise_options, width * height, |freq| {
+		let mut settings = NoiseBuilder::gradient_2d(width, height);
+		settings.with_freq(freq).with_seed(seed);
+		settings.generate_scaled(-1.0, 1.0)
+	})
+}
+
+/// Computes the [fractal sum](NoiseOptions#structfield.octaves) of a 3D gradient noise field seeded with `seed`
+/// and sampled over `frames` steps of `time_step` along its z axis, normalized into
+/// <math><mo>[</mo><mn>0</mn><mo>,</mo><mn>1</mn><mo>]</mo></math>
 ///
-/// # Errors
-/// This function will return an [`OutOfRangeValue`] error if any of the following conditions is false:
-/// - <math><mn>0</mn><mo>≤</mo><mi>hue</mi><mo><</mo><mn>360</mn></math>
-/// - <math><mn>0</mn><mo>≤</mo><mi>saturation</mi><mo>≤</mo><mn>1</mn></math>
-/// - <math><mn>0</mn><mo>≤</mo><mi>brightness</mi><mo>≤</mo><mn>1</mn></math>
-pub fn hsv_to_rgb(hue: f32, saturation: f32, brightness: f32) -> Result<Pixel, OutOfRangeValue> {
-	if !(0.0..360.0).contains(&hue) {
-		return Err(OutOfRangeValue::Hue);
-	}
-	if !(0.0..=1.0).contains(&saturation) {
-		return Err(OutOfRangeValue::Saturation);
+/// The returned buffer stacks `frames` `size.width * size.height` planes back to back, one per frame.
+fn fractal_sum_3d(size: Size, noise_options: NoiseOptions, seed: i32, frames: u32, time_step: f32) -> Vec<f32> {
+	use simdnoise::NoiseBuilder;
+
+	let (width, height, depth) = (size.width as usize, size.height as usize, frames as usize);
+	accumulate_octaves(noise_options, width * height * depth, |freq| {
+		let mut settings = NoiseBuilder::gradient_3d(width, height, depth);
+		settings.with_freq_3d(freq, freq, time_step).with_seed(seed);
+		settings.generate_scaled(-1.0, 1.0)
+	})
+}
+
+/// Accumulates [`noise_options.octaves`](NoiseOptions::octaves) layers of `len` noise values each, produced by
+/// calling `layer` with the frequency of each successive octave, and normalizes the running sum back into
+/// <math><mo>[</mo><mn>0</mn><mo>,</mo><mn>1</mn><mo>]</mo></math>
+fn accumulate_octaves(
+	noise_options: NoiseOptions,
+	len: usize,
+	mut layer: impl FnMut(f32) -> Vec<f32>,
+) -> Vec<f32> {
+	let mut sum = vec![0.0; len];
+	let mut amplitude_sum = 0.0;
+	let mut freq = noise_options.frequency;
+	let mut amp = 1.0;
+	for _ in 0..noise_options.octaves {
+		let octave = layer(freq);
+		for (total, sample) in sum.iter_mut().zip(octave) {
+			*total += amp * if noise_options.turbulence {
+				sample.abs()
+			} else {
+				sample
+			};
+		}
+
+		amplitude_sum += amp;
+		freq *= noise_options.lacunarity;
+		amp *= noise_options.persistence;
 	}
-	if !(0.0..=1.0).contains(&brightness) {
-		return Err(OutOfRangeValue::Brightness);
+
+	for total in &mut sum {
+		let normalized = *total / amplitude_sum;
+		*total = if noise_options.turbulence {
+			normalized
+		} else {
+			(normalized + 1.0) / 2.0
+		};
 	}
 
-	let c = saturation * brightness;
-	let x = c * (1.0 - ((hue / 60.0) % 2.0 - 1.0).abs());
-	let m = brightness - c;
-	let (r, g, b) = if hue < 60.0 {
-		(c, x, 0.0)
-	} else if hue < 120.0 {
-		(x, c, 0.0)
-	} else if hue < 180.0 {
-		(0.0, c, x)
-	} else if hue < 240.0 {
-		(0.0, x, c)
-	} else if hue < 300.0 {
-		(x, 0.0, c)
-	} else {
-		(c, 0.0, x)
-	};
-	Ok(Pixel::new(
-		((r + m) * 255.0) as u8,
-		((g + m) * 255.0) as u8,
-		((b + m) * 255.0) as u8,
-	))
+	sum
 }
 
 #[allow(missing_docs, clippy::missing_docs_in_private_items)]
@@ -405,6 +385,9 @@ pub enum OutOfRangeValue {
 	Hue,
 	Saturation,
 	Brightness,
+	Lightness,
+	OklabChroma,
+	Octaves,
 }
 impl Display for OutOfRangeValue {
 	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
@@ -412,6 +395,9 @@ impl Display for OutOfRangeValue {
 			Self::Hue => write!(f, "Hue is out of range: 0 <= hue < 360"),
 			Self::Saturation => write!(f, "Saturation is out of range: 0 <= saturation <= 1"),
 			Self::Brightness => write!(f, "Brightness is out of range: 0 <= brightness <= 1"),
+			Self::Lightness => write!(f, "Lightness is out of range: 0 <= lightness <= 1"),
+			Self::OklabChroma => write!(f, "OKLab chroma (a/b) is out of range: -0.4 <= a, b <= 0.4"),
+			Self::Octaves => write!(f, "Octaves is out of range: octaves must be at least 1"),
 		}
 	}
 }
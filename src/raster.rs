@@ -0,0 +1,140 @@
+//! 16-bit-per-channel pixel buffer, and the output formats it can be encoded into
+
+use std::{
+	fs::File,
+	io::{self, BufWriter},
+	path::Path,
+};
+
+/// A single RGB pixel at 16-bit-per-channel precision
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Pixel16 {
+	/// Red channel
+	pub r: u16,
+	/// Green channel
+	pub g: u16,
+	/// Blue channel
+	pub b: u16,
+}
+
+/// Color depth an image is encoded at when [saved](RasterImage::save)
+///
+/// Only honored by output formats that support it (currently only PNG); other formats always
+/// downcast to 8 bits per channel regardless.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ColorDepth {
+	/// 8 bits per channel
+	#[default]
+	Eight,
+	/// 16 bits per channel
+	Sixteen,
+}
+
+/// A 16-bit-per-channel RGB image buffer
+///
+/// Internally every pixel is carried at full [`Pixel16`] precision; [`save`](Self::save) only
+/// downcasts to 8 bits per channel when the chosen output format or [`ColorDepth`] requires it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RasterImage {
+	/// Image width
+	width: u32,
+	/// Image height
+	height: u32,
+	/// Row-major pixel buffer
+	pixels: Vec<Pixel16>,
+}
+impl RasterImage {
+	/// Creates a black image of the given dimensions
+	#[must_use]
+	pub fn new(width: u32, height: u32) -> Self {
+		Self {
+			width,
+			height,
+			pixels: vec![Pixel16::default(); (width * height) as usize],
+		}
+	}
+
+	/// Returns an iterator over every `(x, y)` coordinate of the image, in row-major order
+	pub fn coordinates(&self) -> impl Iterator<Item = (u32, u32)> + '_ {
+		let width = self.width;
+		(0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+	}
+
+	/// Returns the pixel at `(x, y)`
+	///
+	/// # Panics
+	/// This function will panic if `(x, y)` is out of bounds.
+	#[must_use]
+	pub fn get_pixel(&self, x: u32, y: u32) -> Pixel16 {
+		self.pixels[(self.width * y + x) as usize]
+	}
+
+	/// Sets the pixel at `(x, y)`
+	///
+	/// # Panics
+	/// This function will panic if `(x, y)` is out of bounds.
+	pub fn set_pixel(&mut self, x: u32, y: u32, pixel: Pixel16) {
+		self.pixels[(self.width * y + x) as usize] = pixel;
+	}
+
+	/// Writes this image to `path` at the given `depth`, picking the output format from `path`'s extension
+	///
+	/// Recognizes a `png` extension as PNG; anything else falls back to BMP, which is always 8-bit-per-channel
+	/// regardless of `depth`.
+	///
+	/// # Errors
+	/// This function will return an [`io::Error`] if `path` cannot be created or written to.
+	pub fn save(&self, path: impl AsRef<Path>, depth: ColorDepth) -> io::Result<()> {
+		let path = path.as_ref();
+		match path.extension().and_then(|ext| ext.to_str()) {
+			Some(ext) if ext.eq_ignore_ascii_case("png") => self.write_png(path, depth),
+			_ => self.write_bmp(path),
+		}
+	}
+
+	/// Writes this image as an 8-bit-per-channel BMP
+	fn write_bmp(&self, path: &Path) -> io::Result<()> {
+		let mut image = bmp::Image::new(self.width, self.height);
+		for (x, y) in self.coordinates() {
+			let px = self.get_pixel(x, y);
+			image.set_pixel(x, y, bmp::Pixel::new(downcast(px.r), downcast(px.g), downcast(px.b)));
+		}
+
+		image.save(path).map_err(io::Error::other)
+	}
+
+	/// Writes this image as a PNG, at the given `depth`
+	fn write_png(&self, path: &Path, depth: ColorDepth) -> io::Result<()> {
+		use png::{BitDepth, ColorType, Encoder};
+
+		let writer = BufWriter::new(File::create(path)?);
+		let mut encoder = Encoder::new(writer, self.width, self.height);
+		encoder.set_color(ColorType::Rgb);
+
+		let bytes = match depth {
+			ColorDepth::Eight => {
+				encoder.set_depth(BitDepth::Eight);
+				self.pixels
+					.iter()
+					.flat_map(|px| [downcast(px.r), downcast(px.g), downcast(px.b)])
+					.collect::<Vec<_>>()
+			}
+			ColorDepth::Sixteen => {
+				encoder.set_depth(BitDepth::Sixteen);
+				self.pixels
+					.iter()
+					.flat_map(|px| [px.r, px.g, px.b])
+					.flat_map(u16::to_be_bytes)
+					.collect::<Vec<_>>()
+			}
+		};
+
+		let mut writer = encoder.write_header().map_err(io::Error::other)?;
+		writer.write_image_data(&bytes).map_err(io::Error::other)
+	}
+}
+
+/// Downcasts a 16-bit channel value into its 8-bit equivalent
+pub(crate) const fn downcast(value: u16) -> u8 {
+	(value >> 8) as u8
+}